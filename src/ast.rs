@@ -0,0 +1,203 @@
+use crate::{Span, Token};
+
+#[cfg(test)]
+use crate::{Number, NumberValue};
+
+/// An expression node produced by the [`Parser`].
+#[derive(Debug, PartialEq)]
+pub enum Expr<'a> {
+    Literal(Token<'a>),
+    Unary {
+        op: Token<'a>,
+        rhs: Box<Expr<'a>>,
+    },
+    Binary {
+        lhs: Box<Expr<'a>>,
+        op: Token<'a>,
+        rhs: Box<Expr<'a>>,
+    },
+    Grouping(Box<Expr<'a>>),
+}
+
+/// A statement node produced by the [`Parser`].
+#[derive(Debug, PartialEq)]
+pub enum Stmt<'a> {
+    Print(Expr<'a>),
+    Var {
+        name: &'a str,
+        initializer: Option<Expr<'a>>,
+    },
+    ExprStmt(Expr<'a>),
+}
+
+/// Right binding power used for the prefix `!`/`-` operators; higher than any
+/// binary operator so a unary expression binds tighter than its operands.
+const UNARY_BINDING_POWER: u8 = 13;
+
+/// Left and right binding powers for an infix operator, or `None` when the
+/// token does not start a binary operator.
+fn infix_binding_power(token: &Token) -> Option<(u8, u8)> {
+    Some(match token {
+        Token::Or => (1, 2),
+        Token::And => (3, 4),
+        Token::EqualEqual | Token::BangEqual => (5, 6),
+        Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual => (7, 8),
+        Token::Plus | Token::Minus => (9, 10),
+        Token::Star | Token::Slash => (11, 12),
+        _ => return None,
+    })
+}
+
+/// A recursive-descent parser over the `(Token, Span)` stream produced by
+/// [`Tokenizer::tokenize`](crate::Tokenizer::tokenize), using precedence
+/// climbing for binary operators.
+pub struct Parser<'a> {
+    tokens: Vec<(Token<'a>, Span)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<(Token<'a>, Span)>) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    /// Parse the whole token stream into a sequence of statements.
+    pub fn parse(&mut self) -> Vec<Stmt<'a>> {
+        let mut statements = Vec::new();
+
+        while !matches!(self.peek(), Token::Eof) {
+            statements.push(self.declaration());
+        }
+
+        statements
+    }
+
+    fn declaration(&mut self) -> Stmt<'a> {
+        match self.peek() {
+            Token::Var => {
+                self.advance();
+                self.var_declaration()
+            }
+            Token::Print => {
+                self.advance();
+                let value = self.parse_expr(0);
+                self.consume_semicolon();
+                Stmt::Print(value)
+            }
+            _ => {
+                let expr = self.parse_expr(0);
+                self.consume_semicolon();
+                Stmt::ExprStmt(expr)
+            }
+        }
+    }
+
+    fn var_declaration(&mut self) -> Stmt<'a> {
+        let name = match self.advance() {
+            Token::Identifier(name) => name,
+            _ => "",
+        };
+
+        let initializer = if matches!(self.peek(), Token::Equal) {
+            self.advance();
+            Some(self.parse_expr(0))
+        } else {
+            None
+        };
+
+        self.consume_semicolon();
+        Stmt::Var { name, initializer }
+    }
+
+    /// Parse an expression, consuming only operators whose left binding power
+    /// is at least `min_bp`.
+    fn parse_expr(&mut self, min_bp: u8) -> Expr<'a> {
+        let mut lhs = match self.peek() {
+            Token::Bang | Token::Minus => {
+                let op = self.advance();
+                let rhs = self.parse_expr(UNARY_BINDING_POWER);
+                Expr::Unary {
+                    op,
+                    rhs: Box::new(rhs),
+                }
+            }
+            Token::LeftParen => {
+                self.advance();
+                let inner = self.parse_expr(0);
+                if matches!(self.peek(), Token::RightParen) {
+                    self.advance();
+                }
+                Expr::Grouping(Box::new(inner))
+            }
+            _ => Expr::Literal(self.advance()),
+        };
+
+        loop {
+            let op = *self.peek();
+            let (l_bp, r_bp) = match infix_binding_power(&op) {
+                Some(bp) => bp,
+                None => break,
+            };
+
+            if l_bp < min_bp {
+                break;
+            }
+
+            self.advance();
+            let rhs = self.parse_expr(r_bp);
+            lhs = Expr::Binary {
+                lhs: Box::new(lhs),
+                op,
+                rhs: Box::new(rhs),
+            };
+        }
+
+        lhs
+    }
+
+    fn peek(&self) -> &Token<'a> {
+        &self.tokens[self.pos].0
+    }
+
+    fn advance(&mut self) -> Token<'a> {
+        let token = self.tokens[self.pos].0;
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn consume_semicolon(&mut self) {
+        if matches!(self.peek(), Token::Semicolon) {
+            self.advance();
+        }
+    }
+}
+
+#[test]
+fn parses_with_precedence() {
+    let script = "1 + 2 * 3;";
+    let (tokens, _) = crate::Tokenizer::new(script).tokenize();
+    let statements = Parser::new(tokens).parse();
+
+    // `*` binds tighter than `+`, so the tree is `1 + (2 * 3)`.
+    let number = |digits, value| {
+        Expr::Literal(Token::Number(Number {
+            digits,
+            value: NumberValue::Integer(value),
+        }))
+    };
+
+    assert_eq!(
+        statements,
+        vec![Stmt::ExprStmt(Expr::Binary {
+            lhs: Box::new(number("1", 1)),
+            op: Token::Plus,
+            rhs: Box::new(Expr::Binary {
+                lhs: Box::new(number("2", 2)),
+                op: Token::Star,
+                rhs: Box::new(number("3", 3)),
+            }),
+        })]
+    );
+}
@@ -1,4 +1,6 @@
-#[derive(Debug, PartialEq)]
+mod ast;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 enum Token<'a> {
     LeftParen,
     RightParen,
@@ -23,9 +25,9 @@ enum Token<'a> {
     LessEqual,
 
     // Literals.
-    Identifier,
+    Identifier(&'a str),
     String(&'a str),
-    Number(f32),
+    Number(Number<'a>),
 
     // Keywords.
     And,
@@ -48,176 +50,339 @@ enum Token<'a> {
     Eof,
 }
 
+/// A numeric literal, retaining the raw source digits alongside a value parsed
+/// at full width so integers keep their precision and fractionals stay `f64`
+/// (the width Lox uses) rather than being coerced down to `f32`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Number<'a> {
+    digits: &'a str,
+    value: NumberValue,
+}
+
+/// The parsed value of a [`Number`], preserving whether it was written as an
+/// integer or a fractional literal.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum NumberValue {
+    Integer(i64),
+    Float(f64),
+}
+
+/// Classify an identifier lexeme, returning the matching keyword token or
+/// `Token::Identifier` carrying the original slice when it is not reserved.
+fn match_keyword(lexeme: &str) -> Token<'_> {
+    match lexeme {
+        "and" => Token::And,
+        "class" => Token::Class,
+        "else" => Token::Else,
+        "false" => Token::False,
+        "fun" => Token::Fun,
+        "for" => Token::For,
+        "if" => Token::If,
+        "nil" => Token::Nil,
+        "or" => Token::Or,
+        "print" => Token::Print,
+        "return" => Token::Return,
+        "super" => Token::Super,
+        "this" => Token::This,
+        "true" => Token::True,
+        "var" => Token::Var,
+        "while" => Token::While,
+        _ => Token::Identifier(lexeme),
+    }
+}
+
+/// A half-open byte range `start..end` into the original script, used to point
+/// diagnostics and downstream nodes back at the exact source text of a token.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+/// A recoverable problem detected while scanning the source.
+#[derive(Debug, PartialEq)]
+enum Message {
+    UnexpectedCharacter(char),
+    UnclosedStringLiteral,
+    InvalidNumberLiteral,
+}
+
+/// A single diagnostic: what went wrong and where in the source it happened.
+#[derive(Debug, PartialEq)]
+struct Diagnostic {
+    message: Message,
+    span: Span,
+}
+
+/// Accumulates diagnostics so scanning can continue past an error and report
+/// every problem at once instead of aborting on the first one.
+#[derive(Default)]
+struct Logger {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Logger {
+    fn log(&mut self, message: Message, span: Span) {
+        self.diagnostics.push(Diagnostic { message, span });
+    }
+}
+
 struct Tokenizer<'a> {
     script: &'a str,
+    iter: std::iter::Peekable<std::str::CharIndices<'a>>,
+    logger: Logger,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(script: &'a str) -> Self {
-        Self { script }
+        Self {
+            script,
+            iter: script.char_indices().peekable(),
+            logger: Logger::default(),
+        }
     }
 
-    pub fn tokenize(&self) -> Vec<Token> {
-        let mut tokens = Vec::new();
+    /// Scan and return the next token together with its span, advancing the
+    /// internal iterator. Whitespace and comments are skipped and recoverable
+    /// errors are logged; the stream terminates with `Token::Eof`.
+    pub fn next_token(&mut self) -> (Token<'a>, Span) {
+        loop {
+            let (start, c) = match self.iter.next() {
+                Some(pair) => pair,
+                None => {
+                    let end = self.script.len();
+                    return (Token::Eof, Span { start: end, end });
+                }
+            };
 
-        let mut iter = self.script.chars().enumerate().peekable();
-
-        let mut line = 0usize;
-
-        while let Some(&(i, c)) = iter.peek() {
-            if let Some(token) = match c {
-                '(' => Some(Token::LeftParen),
-                ')' => Some(Token::RightParen),
-                '{' => Some(Token::LeftBrace),
-                '}' => Some(Token::RightBrace),
-                ',' => Some(Token::Comma),
-                ';' => Some(Token::Semicolon),
-                '.' => Some(Token::Dot),
-                '-' => Some(Token::Minus),
-                '+' => Some(Token::Plus),
-                '*' => Some(Token::Star),
+            let token = match c {
+                '(' => Token::LeftParen,
+                ')' => Token::RightParen,
+                '{' => Token::LeftBrace,
+                '}' => Token::RightBrace,
+                ',' => Token::Comma,
+                ';' => Token::Semicolon,
+                '.' => Token::Dot,
+                '-' => Token::Minus,
+                '+' => Token::Plus,
+                '*' => Token::Star,
                 '!' => {
-                    iter.next();
-                    match iter.peek() {
-                        Some((_, '=')) => Some(Token::BangEqual),
-                        Some(_) => Some(Token::Bang),
-                        _ => unimplemented!(),
+                    if matches!(self.iter.peek(), Some((_, '='))) {
+                        self.iter.next();
+                        Token::BangEqual
+                    } else {
+                        Token::Bang
                     }
                 }
                 '=' => {
-                    iter.next();
-                    match iter.peek() {
-                        Some((_, '=')) => {
-                            iter.next();
-                            Some(Token::EqualEqual)
-                        }
-                        Some(_) => Some(Token::Equal),
-                        _ => unimplemented!(),
+                    if matches!(self.iter.peek(), Some((_, '='))) {
+                        self.iter.next();
+                        Token::EqualEqual
+                    } else {
+                        Token::Equal
                     }
                 }
                 '>' => {
-                    iter.next();
-                    match iter.peek() {
-                        Some((_, '=')) => {
-                            iter.next();
-                            Some(Token::GreaterEqual)
-                        }
-                        Some(_) => Some(Token::Greater),
-                        _ => unimplemented!(),
+                    if matches!(self.iter.peek(), Some((_, '='))) {
+                        self.iter.next();
+                        Token::GreaterEqual
+                    } else {
+                        Token::Greater
                     }
                 }
                 '<' => {
-                    iter.next();
-                    match iter.peek() {
-                        Some((_, '=')) => {
-                            iter.next();
-                            Some(Token::LessEqual)
-                        }
-                        Some(_) => Some(Token::Less),
-                        _ => unimplemented!(),
+                    if matches!(self.iter.peek(), Some((_, '='))) {
+                        self.iter.next();
+                        Token::LessEqual
+                    } else {
+                        Token::Less
                     }
                 }
                 '/' => {
-                    iter.next();
-                    match iter.peek() {
-                        Some((_, '/')) => {
-                            while let Some((_, c)) = iter.peek() {
-                                if *c == '\n' {
-                                    break;
-                                } else {
-                                    iter.next();
-                                }
+                    if matches!(self.iter.peek(), Some((_, '/'))) {
+                        while let Some((_, c)) = self.iter.peek() {
+                            if *c == '\n' {
+                                break;
                             }
-                            None
+                            self.iter.next();
                         }
-                        Some(_) => Some(Token::Slash),
-                        _ => unimplemented!(),
+                        continue;
                     }
+                    Token::Slash
                 }
                 '"' => {
-                    iter.next();
-
-                    let chars = iter
-                        .clone()
-                        .take_while(|(_, c)| {
-                            if *c == '\n' {
-                                line += 1
+                    let content_start = self.peek_offset();
+                    let content_end;
+                    loop {
+                        match self.iter.peek() {
+                            Some(&(i, '"')) => {
+                                content_end = i;
+                                self.iter.next();
+                                break;
                             }
-
-                            if *c != '"' {
-                                iter.next();
+                            Some(_) => {
+                                self.iter.next();
                             }
-
-                            *c != '"'
-                        })
-                        .count();
-
-                    match iter.peek() {
-                        Some((_, '"')) => {}
-                        _ => panic!("Missing closing \" in line {}", line),
+                            None => {
+                                content_end = self.script.len();
+                                self.logger.log(
+                                    Message::UnclosedStringLiteral,
+                                    Span {
+                                        start,
+                                        end: content_end,
+                                    },
+                                );
+                                break;
+                            }
+                        }
                     }
-
-                    Some(Token::String(&self.script[i + 1..i + 1 + chars]))
-                }
-                ' ' | '\r' | '\t' => None,
-                '\n' => {
-                    line += 1;
-                    None
+                    Token::String(&self.script[content_start..content_end])
                 }
+                ' ' | '\r' | '\t' | '\n' => continue,
                 _ => {
-                    if c.is_ascii_digit() {
-                        if let Ok(number) = self.script[i..i + iter
-                            .clone()
-                            .take_while(|(_, c)| {
-                                if *c == '\n' {
-                                    line += 1
-                                }
+                    if c.is_alphabetic() || c == '_' {
+                        while let Some(&(_, c)) = self.iter.peek() {
+                            if c.is_alphanumeric() || c == '_' {
+                                self.iter.next();
+                            } else {
+                                break;
+                            }
+                        }
+                        let end = self.peek_offset();
+                        match_keyword(&self.script[start..end])
+                    } else if c.is_ascii_digit() {
+                        while let Some(&(_, c)) = self.iter.peek() {
+                            if c.is_ascii_digit() {
+                                self.iter.next();
+                            } else {
+                                break;
+                            }
+                        }
 
-                                if c.is_ascii_digit() || *c == '.' {
-                                    iter.next();
+                        // A `.` only starts a fractional part when a digit
+                        // follows it; otherwise it is a `Dot` (e.g. a method
+                        // call), and a second `.` ends the number.
+                        let mut is_float = false;
+                        if matches!(self.iter.peek(), Some((_, '.'))) {
+                            let mut lookahead = self.iter.clone();
+                            lookahead.next();
+                            if matches!(lookahead.peek(), Some((_, d)) if d.is_ascii_digit()) {
+                                is_float = true;
+                                self.iter.next();
+                                while let Some(&(_, c)) = self.iter.peek() {
+                                    if c.is_ascii_digit() {
+                                        self.iter.next();
+                                    } else {
+                                        break;
+                                    }
                                 }
+                            }
+                        }
 
-                                c.is_ascii_digit() || *c == '.'
-                            })
-                            .count()]
-                            .parse::<f32>()
-                        {
-                            Some(Token::Number(number))
+                        let end = self.peek_offset();
+                        let digits = &self.script[start..end];
+                        let value = if is_float {
+                            match digits.parse::<f64>() {
+                                Ok(value) => NumberValue::Float(value),
+                                Err(_) => {
+                                    self.logger
+                                        .log(Message::InvalidNumberLiteral, Span { start, end });
+                                    continue;
+                                }
+                            }
                         } else {
-                            panic!("Unexpected number literal at line {}", line);
-                        }
+                            match digits.parse::<i64>() {
+                                Ok(value) => NumberValue::Integer(value),
+                                Err(_) => {
+                                    self.logger
+                                        .log(Message::InvalidNumberLiteral, Span { start, end });
+                                    continue;
+                                }
+                            }
+                        };
+                        Token::Number(Number { digits, value })
                     } else {
-                        panic!("Unexpected token at line {}", line);
+                        let end = self.peek_offset();
+                        self.logger
+                            .log(Message::UnexpectedCharacter(c), Span { start, end });
+                        continue;
                     }
                 }
-            } {
-                tokens.push(token);
-            }
+            };
 
-            iter.next();
+            let end = self.peek_offset();
+            return (token, Span { start, end });
         }
-        tokens.push(Token::Eof);
-        tokens
+    }
+
+    /// Eagerly scan the whole script, collecting every token and any
+    /// diagnostics produced along the way.
+    pub fn tokenize(&mut self) -> (Vec<(Token<'a>, Span)>, Vec<Diagnostic>) {
+        let mut tokens = Vec::new();
+
+        loop {
+            let (token, span) = self.next_token();
+            let eof = token == Token::Eof;
+            tokens.push((token, span));
+            if eof {
+                break;
+            }
+        }
+
+        (tokens, std::mem::take(&mut self.logger.diagnostics))
+    }
+
+    /// Byte offset of the next unconsumed character, or the end of the script
+    /// when the iterator is exhausted.
+    fn peek_offset(&mut self) -> usize {
+        self.iter.peek().map_or(self.script.len(), |&(i, _)| i)
     }
 }
 
 fn run(script: String) {
-    for token in Tokenizer::new(script.as_str()).tokenize() {
-        println!("{:?}", &token);
+    let (tokens, diagnostics) = Tokenizer::new(script.as_str()).tokenize();
+
+    for diagnostic in &diagnostics {
+        eprintln!("{:?} @ {:?}", diagnostic.message, diagnostic.span);
+    }
+
+    for statement in ast::Parser::new(tokens).parse() {
+        println!("{:?}", statement);
+    }
+}
+
+/// Read-eval-print loop: prompt for lines on stdin and run each one through
+/// the shared pipeline until the input is closed.
+fn repl() {
+    use std::io::{BufRead, Write};
+
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+
+    loop {
+        print!("> ");
+        std::io::stdout().flush().unwrap();
+
+        line.clear();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => run(std::mem::take(&mut line)),
+            Err(error) => {
+                eprintln!("{}", error);
+                break;
+            }
+        }
     }
 }
 
 fn main() {
-    if let Some(script) = std::env::args()
-        .nth(1)
-        .map(std::path::PathBuf::from)
-        .map(std::fs::read_to_string)
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
-    {
-        run(script);
+    if let Some(path) = std::env::args().nth(1) {
+        match std::fs::read_to_string(&path) {
+            Ok(script) => run(script),
+            Err(error) => eprintln!("Could not read {}: {}", path, error),
+        }
     } else {
-        panic!("Usage: loxide <script>");
+        repl();
     }
 }
 
@@ -231,11 +396,14 @@ mod test {
                            ! * + - / = < > == <= >= != // Operators\n\
                            \"Hello World!\"              // String\n\
                            1234                        // Number\n\
-                           12.34                       // Number";
+                           12.34                       // Number\n\
+                           var foo and while           // Identifier + keywords";
 
         eprintln!("{}", script);
-        let tokenizer = Tokenizer::new(script);
-        let mut tokens = tokenizer.tokenize().into_iter();
+        let mut tokenizer = Tokenizer::new(script);
+        let (tokens, diagnostics) = tokenizer.tokenize();
+        assert!(diagnostics.is_empty());
+        let mut tokens = tokens.into_iter().map(|(token, _)| token);
 
         assert_eq!(tokens.next(), Some(Token::LeftParen));
         assert_eq!(tokens.next(), Some(Token::LeftParen));
@@ -259,11 +427,93 @@ mod test {
 
         assert_eq!(tokens.next(), Some(Token::String("Hello World!")));
 
-        assert_eq!(tokens.next(), Some(Token::Number(1234.)));
+        assert_eq!(
+            tokens.next(),
+            Some(Token::Number(Number {
+                digits: "1234",
+                value: NumberValue::Integer(1234),
+            }))
+        );
+
+        assert_eq!(
+            tokens.next(),
+            Some(Token::Number(Number {
+                digits: "12.34",
+                value: NumberValue::Float(12.34),
+            }))
+        );
 
-        assert_eq!(tokens.next(), Some(Token::Number(12.34)));
+        assert_eq!(tokens.next(), Some(Token::Var));
+        assert_eq!(tokens.next(), Some(Token::Identifier("foo")));
+        assert_eq!(tokens.next(), Some(Token::And));
+        assert_eq!(tokens.next(), Some(Token::While));
 
         assert_eq!(tokens.next(), Some(Token::Eof));
         assert_eq!(tokens.next(), None);
     }
+
+    #[test]
+    fn recovers_from_errors() {
+        let script = "@ + #";
+
+        let (tokens, diagnostics) = Tokenizer::new(script).tokenize();
+
+        // Scanning continues past both bad characters, still yielding the `+`.
+        assert_eq!(
+            tokens.into_iter().map(|(token, _)| token).collect::<Vec<_>>(),
+            vec![Token::Plus, Token::Eof]
+        );
+
+        assert_eq!(
+            diagnostics.iter().map(|d| &d.message).collect::<Vec<_>>(),
+            vec![
+                &Message::UnexpectedCharacter('@'),
+                &Message::UnexpectedCharacter('#'),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_stop_at_a_second_dot() {
+        let (tokens, diagnostics) = Tokenizer::new("1.2.3").tokenize();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(
+            tokens.into_iter().map(|(token, _)| token).collect::<Vec<_>>(),
+            vec![
+                Token::Number(Number {
+                    digits: "1.2",
+                    value: NumberValue::Float(1.2),
+                }),
+                Token::Dot,
+                Token::Number(Number {
+                    digits: "3",
+                    value: NumberValue::Integer(3),
+                }),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn streams_one_token_at_a_time() {
+        let mut tokenizer = Tokenizer::new("1 + 2");
+
+        assert_eq!(
+            tokenizer.next_token().0,
+            Token::Number(Number {
+                digits: "1",
+                value: NumberValue::Integer(1),
+            })
+        );
+        assert_eq!(tokenizer.next_token().0, Token::Plus);
+        assert_eq!(
+            tokenizer.next_token().0,
+            Token::Number(Number {
+                digits: "2",
+                value: NumberValue::Integer(2),
+            })
+        );
+        assert_eq!(tokenizer.next_token().0, Token::Eof);
+    }
 }